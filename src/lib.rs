@@ -12,9 +12,29 @@
 #[deny(missing_docs)]
 
 /// Collection of generated prime numbers.
+///
+/// Internally, the sieve of Eratosthenes is bit-packed: only odd numbers are
+/// tracked (one bit each), since no even number other than 2 is prime. This
+/// cuts the sieve's memory use roughly 16x compared to a `Vec<bool>`, so
+/// [`generate_to`], [`is_prime`], [`primes_in_range`], and [`nth_prime`] can
+/// reach into the hundreds of millions in reasonable RAM.
+///
+/// [`factorize`] additionally builds and caches a smallest-prime-factor
+/// table (one `usize` per number), but only lazily: it is grown, to cover the
+/// whole generated sieve, when a number past its end is factorized — callers
+/// who never factorize don't pay for it.
+///
+/// [`generate_to`]: Primes::generate_to
+/// [`is_prime`]: Primes::is_prime
+/// [`primes_in_range`]: Primes::primes_in_range
+/// [`nth_prime`]: Primes::nth_prime
+/// [`factorize`]: Primes::factorize
 pub struct Primes {
-    sieve: Vec<bool>,
+    sieve: Vec<u64>,
+    len: usize,
     primes: Vec<usize>,
+    spf: Vec<usize>,
+    spf_len: usize,
 }
 
 impl Primes {
@@ -23,7 +43,31 @@ impl Primes {
     ///
     /// [`Primes`]: Primes
     pub fn new() -> Self {
-        Primes { sieve: vec![true, true, false, false], primes: vec![2, 3] }
+        Primes {
+            sieve: vec![0],
+            len: 4,
+            primes: vec![2, 3],
+            spf: vec![0, 0, 2, 3],
+            spf_len: 4,
+        }
+    }
+
+    /// Returns the number of bits needed to track every odd number in
+    /// `3..len`.
+    fn bit_count(len: usize) -> usize {
+        len.saturating_sub(2) / 2
+    }
+
+    /// Returns the bit index of the odd number `n` (`n` must be odd and at
+    /// least 3).
+    fn bit_index(n: usize) -> usize {
+        (n - 3) / 2
+    }
+
+    /// Marks the odd number `n` as composite in the bit-packed sieve.
+    fn set_composite(&mut self, n: usize) {
+        let i = Self::bit_index(n);
+        self.sieve[i / 64] |= 1 << (i % 64);
     }
 
     /// Generates primes up to at least `n`.
@@ -47,37 +91,142 @@ impl Primes {
     /// }
     /// ```
     pub fn generate_to(&mut self, n: usize) {
-        if self.sieve.len() > n {
+        if self.len > n {
             return;
         }
 
-        let old_len = self.sieve.len();
+        let old_len = self.len;
 
         {
             let len = (n + 1).next_power_of_two();
-            self.sieve.reserve(len - self.sieve.len());
+            let words = (Self::bit_count(len) + 63) / 64;
+
+            self.sieve.reserve(words - self.sieve.len());
 
-            while self.sieve.len() < len {
-                self.sieve.push(false);
+            while self.sieve.len() < words {
+                self.sieve.push(0);
             }
+
+            self.len = len;
         }
 
-        for prime in &self.primes {
-            for number in ((old_len + prime - 1) / prime * prime
-                ..self.sieve.len())
-                .step_by(*prime)
-            {
-                self.sieve[number] = true;
+        for &prime in &self.primes {
+            if prime == 2 {
+                continue;
+            }
+
+            let mut number = (old_len + prime - 1) / prime * prime;
+
+            if number % 2 == 0 {
+                number += prime;
+            }
+
+            while number < self.len {
+                let i = Self::bit_index(number);
+                self.sieve[i / 64] |= 1 << (i % 64);
+
+                number += 2 * prime;
             }
         }
 
-        for prime in old_len..self.sieve.len() {
-            if !self.sieve[prime] {
-                for number in (prime * prime..self.sieve.len()).step_by(prime) {
-                    self.sieve[number] = true;
+        {
+            let mut number = old_len.max(3);
+
+            if number % 2 == 0 {
+                number += 1;
+            }
+
+            while number < self.len {
+                if !self.is_composite(number) {
+                    let mut multiple = number * number;
+
+                    while multiple < self.len {
+                        self.set_composite(multiple);
+
+                        multiple += 2 * number;
+                    }
+
+                    self.primes.push(number);
                 }
 
-                self.primes.push(prime);
+                number += 2;
+            }
+        }
+    }
+
+    /// Grows the smallest-prime-factor table to cover at least `n`,
+    /// generating primes up to `n` first if needed.
+    ///
+    /// Unlike the bit-packed sieve, this table costs a full `usize` per
+    /// number, so it is only grown when [`factorize`] actually needs it
+    /// rather than on every [`generate_to`] call.
+    ///
+    /// [`factorize`]: Primes::factorize
+    /// [`generate_to`]: Primes::generate_to
+    fn ensure_spf_to(&mut self, n: usize) {
+        self.generate_to(n);
+
+        if self.spf_len > n {
+            return;
+        }
+
+        let old_spf_len = self.spf_len;
+
+        self.spf.reserve(self.len - self.spf.len());
+
+        while self.spf.len() < self.len {
+            self.spf.push(0);
+        }
+
+        self.spf_len = self.len;
+
+        {
+            let mut number = old_spf_len.max(2);
+
+            if number % 2 != 0 {
+                number += 1;
+            }
+
+            while number < self.spf_len {
+                self.spf[number] = 2;
+                number += 2;
+            }
+        }
+
+        {
+            let mut number = old_spf_len.max(3);
+
+            if number % 2 == 0 {
+                number += 1;
+            }
+
+            while number < self.spf_len {
+                if !self.is_composite(number) {
+                    self.spf[number] = number;
+                }
+
+                number += 2;
+            }
+        }
+
+        for &prime in &self.primes {
+            if prime == 2 {
+                continue;
+            }
+
+            let start = old_spf_len.max(prime * prime);
+            let mut number = (start + prime - 1) / prime * prime;
+
+            if number % 2 == 0 {
+                number += prime;
+            }
+
+            while number < self.spf_len {
+                if self.spf[number] == 0 {
+                    self.spf[number] = prime;
+                }
+
+                number += 2 * prime;
             }
         }
     }
@@ -100,7 +249,7 @@ impl Primes {
     /// ```
     pub fn generate_amount(&mut self, amount: usize) {
         while self.primes.len() <= amount {
-            self.generate_to(self.sieve.len());
+            self.generate_to(self.len);
         }
     }
 
@@ -108,8 +257,8 @@ impl Primes {
     ///
     /// This method works faster the more primes are generated.
     pub fn is_prime(&self, n: usize) -> bool {
-        if self.sieve.len() > n {
-            !self.sieve[n]
+        if self.len > n {
+            !self.is_composite(n)
         } else if n % 2 == 0 || n % 3 == 0 {
             false
         } else {
@@ -149,9 +298,14 @@ impl Primes {
         }
     }
 
-    /// Returns an immutable reference to the underlying sieve of Eratosthenes.
+    /// Checks whether `n` is marked as composite (i.e. not prime) in the
+    /// generated sieve.
     ///
-    /// To check if number is in the sieve, simply use it as the index.
+    /// Unlike [`is_prime`], this only looks at already-generated numbers: any
+    /// `n` up to the largest bound passed to [`generate_to`] so far (or
+    /// reached through [`generate_amount`]) is in range. The sieve is rounded
+    /// up to a power of two, so it may reach somewhat further; an `n` past
+    /// its end panics.
     ///
     /// # Example
     ///
@@ -161,11 +315,25 @@ impl Primes {
     /// fn main() {
     ///     let mut primes = Primes::new();
     ///     primes.generate_to(10);
-    ///     assert!(primes.sieve()[10]); // 10 is not prime
+    ///     assert!(primes.is_composite(10)); // 10 is not prime
     /// }
     /// ```
-    pub fn sieve(&self) -> &[bool] {
-        &self.sieve
+    ///
+    /// [`is_prime`]: Primes::is_prime
+    /// [`generate_to`]: Primes::generate_to
+    /// [`generate_amount`]: Primes::generate_amount
+    pub fn is_composite(&self, n: usize) -> bool {
+        assert!(n < self.len, "{n} is out of the generated sieve's range");
+
+        match n {
+            0 | 1 => true,
+            2 => false,
+            n if n % 2 == 0 => true,
+            n => {
+                let i = Self::bit_index(n);
+                (self.sieve[i / 64] >> (i % 64)) & 1 != 0
+            }
+        }
     }
 
     /// Returns an immutable reference to the underlying [`Vec`] of generated
@@ -180,6 +348,287 @@ impl Primes {
     pub fn iter(&mut self) -> Iter {
         self.into_iter()
     }
+
+    /// Returns the largest `r` such that `r * r <= n`.
+    fn isqrt(n: usize) -> usize {
+        let mut r = (n as f64).sqrt() as usize;
+
+        while r * r > n {
+            r -= 1;
+        }
+
+        while (r + 1) * (r + 1) <= n {
+            r += 1;
+        }
+
+        r
+    }
+
+    /// Returns every prime in `lo..=hi`, sieving only the `[lo, hi]` window
+    /// instead of materializing the whole `[0, hi]` sieve.
+    ///
+    /// This generates base primes up to `floor(sqrt(hi))` if they have not
+    /// been generated already, then uses them to sieve just the requested
+    /// window. This makes inspecting a narrow, high range much cheaper than
+    /// calling [`generate_to`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use primter::Primes;
+    ///
+    /// fn main() {
+    ///     let mut primes = Primes::new();
+    ///
+    ///     assert_eq!(primes.primes_in_range(100, 110), [101, 103, 107, 109]);
+    /// }
+    /// ```
+    ///
+    /// [`generate_to`]: Primes::generate_to
+    pub fn primes_in_range(&mut self, lo: usize, hi: usize) -> Vec<usize> {
+        if lo > hi {
+            return Vec::new();
+        }
+
+        self.generate_to(Self::isqrt(hi));
+
+        let mut composite = vec![false; hi - lo + 1];
+
+        for &prime in self.primes() {
+            if prime * prime > hi {
+                break;
+            }
+
+            let start = lo.max(prime * prime);
+            let mut number = (start + prime - 1) / prime * prime;
+
+            while number <= hi {
+                composite[number - lo] = true;
+                number += prime;
+            }
+        }
+
+        (lo..=hi)
+            .zip(composite)
+            .filter(|&(number, is_composite)| number >= 2 && !is_composite)
+            .map(|(number, _)| number)
+            .collect()
+    }
+
+    /// Constructs an iterator over the prime factors of `n`, in non-decreasing
+    /// order, with multiplicity.
+    ///
+    /// This generates primes (and the smallest-prime-factor sieve) up to `n`
+    /// if they have not been generated already, so that every factor can be
+    /// read out of the sieve directly in O(log n) lookups rather than by
+    /// trial division. `n` of 0 or 1 yields no factors.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use primter::Primes;
+    ///
+    /// fn main() {
+    ///     let mut primes = Primes::new();
+    ///     let factors: Vec<_> = primes.factorize(360).collect();
+    ///
+    ///     assert_eq!(factors, [2, 2, 2, 3, 3, 5]);
+    /// }
+    /// ```
+    pub fn factorize(&mut self, n: usize) -> PrimeFactors {
+        self.ensure_spf_to(n);
+
+        PrimeFactors { primes: self, n }
+    }
+
+    /// Returns a bit-view of the generated sieve.
+    ///
+    /// Its `is_composite` accepts the same range of `n` as
+    /// [`is_composite`]: anything up to the largest bound passed to
+    /// [`generate_to`] so far (or reached through [`generate_amount`]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use primter::Primes;
+    ///
+    /// fn main() {
+    ///     let mut primes = Primes::new();
+    ///     primes.generate_to(10);
+    ///     assert!(primes.sieve().is_composite(10)); // 10 is not prime
+    /// }
+    /// ```
+    ///
+    /// [`generate_to`]: Primes::generate_to
+    /// [`generate_amount`]: Primes::generate_amount
+    /// [`is_composite`]: Primes::is_composite
+    pub fn sieve(&self) -> Sieve {
+        Sieve { primes: self }
+    }
+
+    /// Returns the `n`-th prime (1-indexed, so `nth_prime(1) == 2`).
+    ///
+    /// Rather than growing the sieve by repeated doubling until it contains
+    /// enough primes, this estimates an upper bound for the `n`-th prime
+    /// ahead of time and sieves it in one pass.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0, since there is no 0th prime.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use primter::Primes;
+    ///
+    /// fn main() {
+    ///     let mut primes = Primes::new();
+    ///
+    ///     assert_eq!(primes.nth_prime(6), 13);
+    /// }
+    /// ```
+    pub fn nth_prime(&mut self, n: usize) -> usize {
+        assert!(n >= 1, "there is no 0th prime");
+
+        const SMALL: [usize; 5] = [2, 3, 5, 7, 11];
+
+        if n <= SMALL.len() {
+            return SMALL[n - 1];
+        }
+
+        let n_f = n as f64;
+        let bound = (n_f * (n_f.ln() + n_f.ln().ln())).ceil() as usize;
+
+        self.generate_to(bound);
+
+        self.primes[n - 1]
+    }
+}
+
+/// Bit-view of a [`Primes`]'s generated sieve.
+///
+/// Constructed by [`Primes::sieve`].
+///
+/// [`Primes`]: Primes
+/// [`Primes::sieve`]: Primes::sieve
+pub struct Sieve<'a> {
+    primes: &'a Primes,
+}
+
+impl<'a> Sieve<'a> {
+    /// Checks whether `n` is marked as composite (i.e. not prime) in the
+    /// generated sieve.
+    ///
+    /// See [`Primes::is_composite`] for the panic condition on `n`.
+    ///
+    /// [`Primes::is_composite`]: Primes::is_composite
+    pub fn is_composite(&self, n: usize) -> bool {
+        self.primes.is_composite(n)
+    }
+}
+
+/// Iterator over the prime factors of a number, in non-decreasing order,
+/// with multiplicity.
+///
+/// Constructed by [`Primes::factorize`].
+///
+/// [`Primes::factorize`]: Primes::factorize
+pub struct PrimeFactors<'a> {
+    primes: &'a Primes,
+    n: usize,
+}
+
+impl<'a> PrimeFactors<'a> {
+    /// Looks up the smallest prime factor of `self.n` in the sieve.
+    ///
+    /// `Primes::factorize` always sieves up to the original `n` before
+    /// constructing this iterator, and `self.n` only ever shrinks from
+    /// there, so `self.n` is always in range.
+    fn smallest_prime_factor(&mut self) -> usize {
+        self.primes.spf[self.n]
+    }
+
+    /// Returns each distinct prime factor once, skipping repeats caused by
+    /// multiplicity.
+    ///
+    /// Since factors come out in non-decreasing order, this is done by
+    /// simply skipping a factor equal to the previously yielded one.
+    pub fn unique(self) -> Unique<'a> {
+        Unique { factors: self, last: None }
+    }
+
+    /// Groups consecutive equal prime factors into `(prime, exponent)`
+    /// pairs.
+    pub fn rle(self) -> Rle<'a> {
+        Rle { factors: self.peekable() }
+    }
+}
+
+impl<'a> Iterator for PrimeFactors<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.n == 0 || self.n == 1 {
+            return None;
+        }
+
+        let prime = self.smallest_prime_factor();
+        self.n /= prime;
+
+        Some(prime)
+    }
+}
+
+/// Iterator over the distinct prime factors of a number, skipping repeats.
+///
+/// Constructed by [`PrimeFactors::unique`].
+///
+/// [`PrimeFactors::unique`]: PrimeFactors::unique
+pub struct Unique<'a> {
+    factors: PrimeFactors<'a>,
+    last: Option<usize>,
+}
+
+impl<'a> Iterator for Unique<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for prime in self.factors.by_ref() {
+            if self.last != Some(prime) {
+                self.last = Some(prime);
+
+                return Some(prime);
+            }
+        }
+
+        None
+    }
+}
+
+/// Iterator over the prime factors of a number, run-length encoded as
+/// `(prime, exponent)` pairs.
+///
+/// Constructed by [`PrimeFactors::rle`].
+///
+/// [`PrimeFactors::rle`]: PrimeFactors::rle
+pub struct Rle<'a> {
+    factors: std::iter::Peekable<PrimeFactors<'a>>,
+}
+
+impl<'a> Iterator for Rle<'a> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let prime = self.factors.next()?;
+        let mut exponent = 1;
+
+        while self.factors.peek() == Some(&prime) {
+            self.factors.next();
+            exponent += 1;
+        }
+
+        Some((prime, exponent))
+    }
 }
 
 impl IntoIterator for Primes {
@@ -217,6 +666,13 @@ impl Iterator for IntoIter {
 
         Some(self.primes.primes()[self.index - 1])
     }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.index += n + 1;
+        self.primes.generate_amount(self.index);
+
+        Some(self.primes.primes()[self.index - 1])
+    }
 }
 
 /// Borrowed iterator for [`Primes`].
@@ -227,6 +683,29 @@ pub struct Iter<'a> {
     index: usize,
 }
 
+impl<'a> Iter<'a> {
+    /// Seeks the iterator to the first prime greater than or equal to
+    /// `value`, generating more primes as needed.
+    ///
+    /// Since the cached primes are sorted, the target position is found by
+    /// binary search instead of stepping through the iterator one prime at
+    /// a time. Note that `value` may be smaller than the current position,
+    /// in which case this moves the iterator backward.
+    pub fn skip_to(&mut self, value: usize) -> &mut Self {
+        self.primes.generate_to(value);
+
+        while *self.primes.primes().last().unwrap() < value {
+            self.primes.generate_to(self.primes.len);
+        }
+
+        self.index = match self.primes.primes().binary_search(&value) {
+            Ok(index) | Err(index) => index,
+        };
+
+        self
+    }
+}
+
 impl<'a> Iterator for Iter<'a> {
     type Item = usize;
 
@@ -236,6 +715,13 @@ impl<'a> Iterator for Iter<'a> {
 
         Some(self.primes.primes()[self.index - 1])
     }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.index += n + 1;
+        self.primes.generate_amount(self.index);
+
+        Some(self.primes.primes()[self.index - 1])
+    }
 }
 
 #[cfg(test)]
@@ -244,14 +730,20 @@ mod tests {
 
     #[test]
     fn empty() {
-        assert_eq!(Primes::new().sieve, [true, true, false, false]);
+        let primes = Primes::new();
+
+        assert_eq!(primes.is_composite(0), true);
+        assert_eq!(primes.is_composite(1), true);
+        assert_eq!(primes.is_composite(2), false);
+        assert_eq!(primes.is_composite(3), false);
     }
 
     #[test]
     fn len() {
         let mut primes = Primes::new();
         primes.generate_to(4);
-        assert_eq!(primes.sieve().len(), 8);
+
+        assert_eq!(primes.len, 8);
     }
 
     #[test]
@@ -262,7 +754,7 @@ mod tests {
         assert_eq!(primes.primes(), [2, 3, 5, 7, 11, 13]);
 
         assert_eq!(
-            primes.sieve(),
+            (0..16).map(|n| primes.is_composite(n)).collect::<Vec<_>>(),
             [
                 true, true, false, false, true, false, true, false, true, true,
                 true, false, true, false, true, true
@@ -274,7 +766,7 @@ mod tests {
         assert_eq!(primes.primes(), [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31]);
 
         assert_eq!(
-            primes.sieve(),
+            (0..32).map(|n| primes.is_composite(n)).collect::<Vec<_>>(),
             [
                 true, true, false, false, true, false, true, false, true, true,
                 true, false, true, false, true, true, true, false, true, false,
@@ -330,4 +822,73 @@ mod tests {
 
         assert_eq!(result, [2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
     }
+
+    #[test]
+    fn factorize() {
+        let mut primes = Primes::new();
+
+        assert_eq!(primes.factorize(0).collect::<Vec<_>>(), []);
+        assert_eq!(primes.factorize(1).collect::<Vec<_>>(), []);
+        assert_eq!(primes.factorize(360).collect::<Vec<_>>(), [2, 2, 2, 3, 3, 5]);
+        assert_eq!(primes.factorize(97).collect::<Vec<_>>(), [97]);
+    }
+
+    #[test]
+    fn unique() {
+        let mut primes = Primes::new();
+
+        assert_eq!(primes.factorize(360).unique().collect::<Vec<_>>(), [2, 3, 5]);
+        assert_eq!(primes.factorize(97).unique().collect::<Vec<_>>(), [97]);
+    }
+
+    #[test]
+    fn rle() {
+        let mut primes = Primes::new();
+
+        assert_eq!(
+            primes.factorize(360).rle().collect::<Vec<_>>(),
+            [(2, 3), (3, 2), (5, 1)]
+        );
+        assert_eq!(primes.factorize(97).rle().collect::<Vec<_>>(), [(97, 1)]);
+    }
+
+    #[test]
+    fn primes_in_range() {
+        let mut primes = Primes::new();
+
+        assert_eq!(primes.primes_in_range(100, 110), [101, 103, 107, 109]);
+        assert_eq!(primes.primes_in_range(0, 10), [2, 3, 5, 7]);
+        assert_eq!(primes.primes_in_range(8, 10), []);
+        assert_eq!(primes.primes_in_range(10, 8), []);
+    }
+
+    #[test]
+    fn nth_prime() {
+        let mut primes = Primes::new();
+
+        assert_eq!(primes.nth_prime(1), 2);
+        assert_eq!(primes.nth_prime(5), 11);
+        assert_eq!(primes.nth_prime(6), 13);
+        assert_eq!(primes.nth_prime(100), 541);
+    }
+
+    #[test]
+    fn into_iter_nth() {
+        assert_eq!(Primes::new().into_iter().nth(9), Some(29));
+    }
+
+    #[test]
+    fn iter_nth() {
+        let mut primes = Primes::new();
+        assert_eq!(primes.iter().nth(9), Some(29));
+    }
+
+    #[test]
+    fn skip_to() {
+        let mut primes = Primes::new();
+        let mut iter = primes.iter();
+
+        assert_eq!(iter.skip_to(100).next(), Some(101));
+        assert_eq!(iter.next(), Some(103));
+    }
 }